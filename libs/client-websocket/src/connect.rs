@@ -0,0 +1,49 @@
+use crate::error::Result;
+use crate::headers::{build_handshake_request, ExtraHeadersBuilder};
+use crate::redirect::{follow_redirects, RedirectPolicy};
+
+/// Performs one WebSocket handshake attempt: given the fully-built upgrade request,
+/// exchanges it for the raw HTTP response.
+///
+/// Establishing the underlying connection (TCP, optionally TLS, or the browser APIs on
+/// wasm) is transport-specific and lives outside this crate's core; callers inject it
+/// here so the handshake and redirect logic above it stay transport-agnostic.
+pub trait HandshakeTransport {
+  /// Sends `request` over a connection to its target and returns the response.
+  fn attempt(&mut self, request: &http::Request<()>) -> Result<http::Response<Option<Vec<u8>>>>;
+}
+
+impl<F> HandshakeTransport for F
+where
+  F: FnMut(&http::Request<()>) -> Result<http::Response<Option<Vec<u8>>>>,
+{
+  fn attempt(&mut self, request: &http::Request<()>) -> Result<http::Response<Option<Vec<u8>>>> {
+    self(request)
+  }
+}
+
+/// Connects to `uri` once (no redirect following): builds the upgrade request with
+/// `extra_headers` attached via [`build_handshake_request`], then hands it to
+/// `transport`.
+pub fn connect_once(
+  uri: http::Uri,
+  extra_headers: ExtraHeadersBuilder,
+  mut transport: impl HandshakeTransport,
+) -> Result<http::Response<Option<Vec<u8>>>> {
+  let request = build_handshake_request(uri, extra_headers)?;
+  transport.attempt(&request)
+}
+
+/// Connects to `uri`, attaching `extra_headers` to the upgrade request and following
+/// `301`/`302`/`307`/`308` responses per `redirect_policy` (rebuilding the request, including a fresh
+/// `Sec-WebSocket-Key`, for each hop). The returned response's extensions carry a
+/// [`crate::redirect::FinalUrl`] reflecting the last URL actually reached.
+pub fn connect(
+  uri: http::Uri,
+  extra_headers: ExtraHeadersBuilder,
+  redirect_policy: RedirectPolicy,
+  mut transport: impl HandshakeTransport,
+) -> Result<http::Response<Option<Vec<u8>>>> {
+  let request = build_handshake_request(uri, extra_headers)?;
+  follow_redirects(request, redirect_policy, |req| transport.attempt(req))
+}
@@ -1,5 +1,5 @@
 use http::{header::HeaderName, Response};
-use std::{io, result, str, string};
+use std::{borrow::Cow, io, result, str, string};
 use thiserror::Error;
 use tokio_tungstenite::tungstenite::http;
 
@@ -20,8 +20,11 @@ pub enum Error {
   ///
   /// Receiving this error means that the WebSocket object is not usable anymore and the
   /// only meaningful action with it is dropping it.
-  #[error("Connection closed normally")]
-  ConnectionClosed,
+  ///
+  /// Carries the close frame sent or received during the close handshake, if any, so
+  /// callers can tell a clean shutdown from a policy/size rejection or a server going away.
+  #[error("Connection closed{}", .0.as_ref().map(|f| format!(": {f}")).unwrap_or_default())]
+  ConnectionClosed(Option<CloseFrame>),
   /// Trying to work with already closed connection.
   ///
   /// Trying to read or write after receiving `ConnectionClosed` causes this.
@@ -69,6 +72,69 @@ pub enum Error {
   UnknownFormat,
 }
 
+/// Broad classification of an [`Error`], used to decide whether a reconnect loop
+/// should retry (optionally with backoff) or give up.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ErrorCategory {
+  /// Likely to clear up on its own; retrying, ideally with backoff, is reasonable.
+  Transient,
+  /// A WebSocket protocol violation by either end, or data the connection cannot
+  /// handle (capacity limits, invalid UTF-8, unsupported or unknown data formats);
+  /// reconnecting won't help.
+  Protocol,
+  /// Unrecoverable; the caller should stop trying and surface the error.
+  Fatal,
+  /// The connection was closed per the close handshake. This is the "retryable" bucket
+  /// for `ConnectionClosed` specifically: unlike a 5xx `Http` error, whether to
+  /// reconnect depends on the close code carried in the `CloseFrame` (e.g. `Normal`
+  /// likely shouldn't reconnect, `GoingAway`/`Abnormal` likely should), so it gets its
+  /// own category rather than being folded into `Transient`.
+  Closed,
+}
+
+impl Error {
+  /// Returns the broad [`ErrorCategory`] this error falls into, so callers can decide
+  /// whether to retry with backoff or give up without matching on every variant.
+  pub fn category(&self) -> ErrorCategory {
+    match self {
+      Error::ConnectionClosed(_) => ErrorCategory::Closed,
+      Error::AlreadyClosed => ErrorCategory::Fatal,
+      Error::Io(err) => match err.kind() {
+        io::ErrorKind::WouldBlock
+        | io::ErrorKind::Interrupted
+        | io::ErrorKind::TimedOut
+        | io::ErrorKind::ConnectionReset => ErrorCategory::Transient,
+        _ => ErrorCategory::Fatal,
+      },
+      #[cfg(not(target_arch = "wasm32"))]
+      Error::Tls(_) => ErrorCategory::Fatal,
+      Error::Capacity(_) => ErrorCategory::Protocol,
+      Error::Protocol(_) => ErrorCategory::Protocol,
+      Error::WriteBufferFull(_) => ErrorCategory::Transient,
+      Error::Utf8 => ErrorCategory::Protocol,
+      Error::AttackAttempt => ErrorCategory::Protocol,
+      Error::Url(_) => ErrorCategory::Fatal,
+      Error::Http(response) => {
+        if response.status().is_server_error() {
+          ErrorCategory::Transient
+        } else {
+          ErrorCategory::Fatal
+        }
+      }
+      Error::HttpFormat(_) => ErrorCategory::Fatal,
+      Error::BlobFormatUnsupported => ErrorCategory::Protocol,
+      Error::UnknownFormat => ErrorCategory::Protocol,
+    }
+  }
+
+  /// Returns `true` if this error falls into [`ErrorCategory::Fatal`]. Errors in
+  /// [`ErrorCategory::Protocol`] return `false` even though reconnecting won't help
+  /// them either; use [`Error::category`] to tell the two apart.
+  pub fn is_fatal(&self) -> bool {
+    self.category() == ErrorCategory::Fatal
+  }
+}
+
 impl From<str::Utf8Error> for Error {
   fn from(_: str::Utf8Error) -> Self {
     Error::Utf8
@@ -242,6 +308,13 @@ pub enum UrlError {
   /// The URL does not include a path/query.
   #[error("No path/query in URL")]
   NoPathOrQuery,
+  /// The handshake followed more HTTP redirects than the configured limit without
+  /// reaching a non-redirect response.
+  #[error("Too many redirects: exceeded limit of {0}")]
+  TooManyRedirects(usize),
+  /// A redirect response (3xx) was missing a usable `Location` header.
+  #[error("Invalid or missing redirect location")]
+  InvalidRedirectLocation,
 }
 
 /// Data opcodes as in RFC 6455
@@ -267,3 +340,244 @@ impl std::fmt::Display for Data {
     }
   }
 }
+
+/// Status code used to indicate why an endpoint is closing the WebSocket connection,
+/// as defined by [RFC 6455 section 7.4](https://datatracker.ietf.org/doc/html/rfc6455#section-7.4).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CloseCode {
+  /// Indicates a normal closure, meaning that the purpose for which the connection was
+  /// established has been fulfilled.
+  Normal,
+  /// Indicates that an endpoint is "going away", such as a server going down or a
+  /// browser having navigated away from a page.
+  GoingAway,
+  /// Indicates that an endpoint is terminating the connection due to a protocol error.
+  ProtocolError,
+  /// Indicates that an endpoint is terminating the connection because it has received
+  /// a type of data it cannot accept.
+  Unsupported,
+  /// Indicates that an endpoint is terminating the connection because it has received
+  /// data within a message that was not consistent with the type of the message.
+  InvalidFramePayloadData,
+  /// Indicates that an endpoint is terminating the connection because it has received
+  /// a message that violates its policy.
+  PolicyViolation,
+  /// Indicates that an endpoint is terminating the connection because it has received
+  /// a message that is too big for it to process.
+  MessageTooBig,
+  /// Indicates that an endpoint (client) is terminating the connection because it has
+  /// expected the server to negotiate one or more extensions, but the server didn't
+  /// return them in the response message of the WebSocket handshake.
+  MandatoryExtension,
+  /// Indicates that a server is terminating the connection because it encountered an
+  /// unexpected condition that prevented it from fulfilling the request.
+  InternalError,
+  /// Reserved. Indicates that the connection was closed due to a failure to perform a
+  /// TLS handshake. This close code is never sent on the wire.
+  TlsHandshake,
+  /// Reserved. Indicates that no status code was present in a close frame. This close
+  /// code is never sent on the wire, only produced locally when none was received.
+  NoStatusReceived,
+  /// Reserved. Indicates that the connection was closed abnormally, e.g. without
+  /// sending or receiving a close frame. This close code is never sent on the wire.
+  Abnormal,
+  /// Close codes in the range 3000-3999 are reserved for use by libraries, frameworks,
+  /// and applications, and may be registered with IANA.
+  Library(u16),
+  /// Close codes in the range 4000-4999 are reserved for private use and thus can't be
+  /// registered. Such codes can be used by prior agreement between WebSocket applications.
+  Private(u16),
+  /// Any other close code that does not fall into one of the categories above.
+  Bad(u16),
+}
+
+impl From<u16> for CloseCode {
+  fn from(code: u16) -> Self {
+    match code {
+      1000 => CloseCode::Normal,
+      1001 => CloseCode::GoingAway,
+      1002 => CloseCode::ProtocolError,
+      1003 => CloseCode::Unsupported,
+      1005 => CloseCode::NoStatusReceived,
+      1006 => CloseCode::Abnormal,
+      1007 => CloseCode::InvalidFramePayloadData,
+      1008 => CloseCode::PolicyViolation,
+      1009 => CloseCode::MessageTooBig,
+      1010 => CloseCode::MandatoryExtension,
+      1011 => CloseCode::InternalError,
+      1015 => CloseCode::TlsHandshake,
+      3000..=3999 => CloseCode::Library(code),
+      4000..=4999 => CloseCode::Private(code),
+      _ => CloseCode::Bad(code),
+    }
+  }
+}
+
+impl From<CloseCode> for u16 {
+  fn from(code: CloseCode) -> Self {
+    match code {
+      CloseCode::Normal => 1000,
+      CloseCode::GoingAway => 1001,
+      CloseCode::ProtocolError => 1002,
+      CloseCode::Unsupported => 1003,
+      CloseCode::NoStatusReceived => 1005,
+      CloseCode::Abnormal => 1006,
+      CloseCode::InvalidFramePayloadData => 1007,
+      CloseCode::PolicyViolation => 1008,
+      CloseCode::MessageTooBig => 1009,
+      CloseCode::MandatoryExtension => 1010,
+      CloseCode::InternalError => 1011,
+      CloseCode::TlsHandshake => 1015,
+      CloseCode::Library(code) | CloseCode::Private(code) | CloseCode::Bad(code) => code,
+    }
+  }
+}
+
+impl std::fmt::Display for CloseCode {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{}", u16::from(*self))
+  }
+}
+
+/// A struct representing the close command.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CloseFrame {
+  /// The reason as a code.
+  pub code: CloseCode,
+  /// The reason as text string.
+  pub reason: Cow<'static, str>,
+}
+
+impl std::fmt::Display for CloseFrame {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{} ({})", self.reason, self.code)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn category_closed_and_already_closed() {
+    assert_eq!(Error::ConnectionClosed(None).category(), ErrorCategory::Closed);
+    assert!(!Error::ConnectionClosed(None).is_fatal());
+    assert_eq!(Error::AlreadyClosed.category(), ErrorCategory::Fatal);
+    assert!(Error::AlreadyClosed.is_fatal());
+  }
+
+  #[test]
+  fn category_io_kinds() {
+    let transient = [
+      io::ErrorKind::WouldBlock,
+      io::ErrorKind::Interrupted,
+      io::ErrorKind::TimedOut,
+      io::ErrorKind::ConnectionReset,
+    ];
+    for kind in transient {
+      let err = Error::Io(io::Error::from(kind));
+      assert_eq!(err.category(), ErrorCategory::Transient);
+      assert!(!err.is_fatal());
+    }
+
+    let err = Error::Io(io::Error::from(io::ErrorKind::PermissionDenied));
+    assert_eq!(err.category(), ErrorCategory::Fatal);
+    assert!(err.is_fatal());
+  }
+
+  #[test]
+  fn category_protocol_and_non_retryable_variants() {
+    assert_eq!(
+      Error::Capacity(CapacityError::TooManyHeaders).category(),
+      ErrorCategory::Protocol
+    );
+    assert_eq!(
+      Error::Protocol(ProtocolError::HandshakeIncomplete).category(),
+      ErrorCategory::Protocol
+    );
+    assert_eq!(Error::Utf8.category(), ErrorCategory::Protocol);
+    assert_eq!(Error::AttackAttempt.category(), ErrorCategory::Protocol);
+    assert_eq!(Error::BlobFormatUnsupported.category(), ErrorCategory::Protocol);
+    assert_eq!(Error::UnknownFormat.category(), ErrorCategory::Protocol);
+    for err in [
+      Error::Capacity(CapacityError::TooManyHeaders),
+      Error::Protocol(ProtocolError::HandshakeIncomplete),
+      Error::Utf8,
+      Error::AttackAttempt,
+      Error::BlobFormatUnsupported,
+      Error::UnknownFormat,
+    ] {
+      assert!(!err.is_fatal());
+    }
+  }
+
+  #[test]
+  fn category_url_and_http_format_are_fatal() {
+    assert_eq!(Error::Url(UrlError::NoHostName).category(), ErrorCategory::Fatal);
+    assert!(Error::Url(UrlError::NoHostName).is_fatal());
+
+    let invalid_status = http::Response::builder().status(1000).body(()).unwrap_err();
+    let err = Error::from(invalid_status);
+    assert_eq!(err.category(), ErrorCategory::Fatal);
+    assert!(err.is_fatal());
+  }
+
+  #[test]
+  fn category_http_splits_on_5xx() {
+    let server_error = Error::Http(Box::new(
+      Response::builder().status(503).body(None).unwrap(),
+    ));
+    assert_eq!(server_error.category(), ErrorCategory::Transient);
+    assert!(!server_error.is_fatal());
+
+    let client_error = Error::Http(Box::new(
+      Response::builder().status(404).body(None).unwrap(),
+    ));
+    assert_eq!(client_error.category(), ErrorCategory::Fatal);
+    assert!(client_error.is_fatal());
+  }
+
+  #[test]
+  fn close_code_named_round_trips() {
+    let named = [
+      (1000u16, CloseCode::Normal),
+      (1001, CloseCode::GoingAway),
+      (1002, CloseCode::ProtocolError),
+      (1003, CloseCode::Unsupported),
+      (1005, CloseCode::NoStatusReceived),
+      (1006, CloseCode::Abnormal),
+      (1007, CloseCode::InvalidFramePayloadData),
+      (1008, CloseCode::PolicyViolation),
+      (1009, CloseCode::MessageTooBig),
+      (1010, CloseCode::MandatoryExtension),
+      (1011, CloseCode::InternalError),
+      (1015, CloseCode::TlsHandshake),
+    ];
+    for (code, expected) in named {
+      assert_eq!(CloseCode::from(code), expected);
+      assert_eq!(u16::from(expected), code);
+    }
+  }
+
+  #[test]
+  fn close_code_reserved_1005_1006_are_never_sent_but_parse_locally() {
+    // 1005/1006 are never sent on the wire, but a locally observed close with no
+    // status (or an abnormal disconnect) must still parse to a named reserved code.
+    assert_eq!(CloseCode::from(1005u16), CloseCode::NoStatusReceived);
+    assert_eq!(CloseCode::from(1006u16), CloseCode::Abnormal);
+  }
+
+  #[test]
+  fn close_code_library_private_and_bad_ranges() {
+    assert_eq!(CloseCode::from(3000u16), CloseCode::Library(3000));
+    assert_eq!(CloseCode::from(3999u16), CloseCode::Library(3999));
+    assert_eq!(CloseCode::from(4000u16), CloseCode::Private(4000));
+    assert_eq!(CloseCode::from(4999u16), CloseCode::Private(4999));
+    assert_eq!(CloseCode::from(2999u16), CloseCode::Bad(2999));
+    assert_eq!(CloseCode::from(5000u16), CloseCode::Bad(5000));
+
+    for code in [3000u16, 3999, 4000, 4999, 2999, 5000] {
+      assert_eq!(u16::from(CloseCode::from(code)), code);
+    }
+  }
+}
@@ -0,0 +1,136 @@
+use http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::handshake::client::generate_key;
+
+use crate::error::{Error, ProtocolError, Result};
+
+/// Handshake headers that the library sets itself and that a caller must not override,
+/// since doing so would desynchronize the upgrade request from what the handshake code
+/// actually sends.
+const RESERVED_HEADERS: &[&str] = &[
+  "connection",
+  "upgrade",
+  "sec-websocket-key",
+  "sec-websocket-version",
+  "host",
+];
+
+/// Builds the extra headers attached to the WebSocket upgrade request, e.g. auth
+/// tokens, workspace IDs, or tracing headers.
+///
+/// Attempting to set one of the headers the handshake itself manages (`Connection`,
+/// `Upgrade`, `Sec-WebSocket-Key`, `Sec-WebSocket-Version`, `Host`) is rejected with
+/// [`ProtocolError::InvalidHeader`] rather than silently overwriting it.
+#[derive(Debug, Default, Clone)]
+pub struct ExtraHeadersBuilder {
+  headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl ExtraHeadersBuilder {
+  /// Creates an empty builder.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a header to be sent with the handshake request.
+  ///
+  /// `name` and `value` may be anything that converts to [`HeaderName`]/[`HeaderValue`]
+  /// (e.g. `&str`); conversion failures flow through the existing
+  /// `InvalidHeaderName`/`InvalidHeaderValue` -> [`Error::HttpFormat`] conversions.
+  /// Returns [`Error::Protocol`] wrapping [`ProtocolError::InvalidHeader`] if `name` is
+  /// one of the headers the handshake manages itself.
+  pub fn header<K, V>(mut self, name: K, value: V) -> Result<Self>
+  where
+    K: TryInto<HeaderName>,
+    V: TryInto<HeaderValue>,
+    Error: From<K::Error> + From<V::Error>,
+  {
+    let name = name.try_into().map_err(Error::from)?;
+    let value = value.try_into().map_err(Error::from)?;
+    if RESERVED_HEADERS.contains(&name.as_str()) {
+      return Err(Error::Protocol(ProtocolError::InvalidHeader(name)));
+    }
+    self.headers.push((name, value));
+    Ok(self)
+  }
+
+  /// Consumes the builder, returning the validated `(HeaderName, HeaderValue)` pairs to
+  /// attach to the upgrade request.
+  pub fn build(self) -> Vec<(HeaderName, HeaderValue)> {
+    self.headers
+  }
+}
+
+/// Builds the client's WebSocket upgrade request for `uri`, setting the
+/// handshake-critical headers itself and then layering on `extra_headers` last, so
+/// validated caller headers are attached at the same point the reserved ones are fixed
+/// and can never be shadowed.
+///
+/// `Sec-WebSocket-Key` is generated fresh on every call (it's a nonce and must not be
+/// reused across handshake attempts). [`crate::redirect::rebuild_for_redirect`] does the
+/// same for each redirect hop, since that path doesn't call back into this function.
+pub fn build_handshake_request(
+  uri: http::Uri,
+  extra_headers: ExtraHeadersBuilder,
+) -> Result<http::Request<()>> {
+  let host = uri
+    .host()
+    .ok_or_else(|| Error::Url(crate::error::UrlError::NoHostName))?;
+  let host_value = match uri.port() {
+    Some(port) => format!("{host}:{port}"),
+    None => host.to_string(),
+  };
+  let mut builder = http::Request::builder()
+    .method(http::Method::GET)
+    .uri(uri)
+    .header(http::header::CONNECTION, "Upgrade")
+    .header(http::header::UPGRADE, "websocket")
+    .header(http::header::SEC_WEBSOCKET_VERSION, "13")
+    .header(http::header::SEC_WEBSOCKET_KEY, generate_key())
+    .header(http::header::HOST, host_value);
+  for (name, value) in extra_headers.build() {
+    builder = builder.header(name, value);
+  }
+  builder.body(()).map_err(Error::HttpFormat)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn reserved_headers_are_rejected() {
+    for reserved in RESERVED_HEADERS {
+      let err = ExtraHeadersBuilder::new()
+        .header(*reserved, "value")
+        .unwrap_err();
+      assert!(matches!(err, Error::Protocol(ProtocolError::InvalidHeader(_))));
+    }
+  }
+
+  #[test]
+  fn reserved_header_check_is_case_insensitive() {
+    let err = ExtraHeadersBuilder::new()
+      .header("Sec-WebSocket-Key", "value")
+      .unwrap_err();
+    assert!(matches!(err, Error::Protocol(ProtocolError::InvalidHeader(_))));
+  }
+
+  #[test]
+  fn non_reserved_headers_are_kept_and_applied() {
+    let extra = ExtraHeadersBuilder::new()
+      .header("x-workspace-id", "abc123")
+      .unwrap()
+      .header("authorization", "Bearer token")
+      .unwrap();
+    assert_eq!(extra.build().len(), 2);
+
+    let request =
+      build_handshake_request("ws://example.com/ws".parse().unwrap(), extra).unwrap();
+    assert_eq!(
+      request.headers().get("x-workspace-id").unwrap(),
+      "abc123"
+    );
+    assert_eq!(request.headers().get("authorization").unwrap(), "Bearer token");
+    assert!(request.headers().get(http::header::SEC_WEBSOCKET_KEY).is_some());
+  }
+}
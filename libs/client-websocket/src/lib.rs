@@ -0,0 +1,6 @@
+pub mod connect;
+pub mod error;
+pub mod headers;
+pub mod redirect;
+
+pub use error::{Error, Result};
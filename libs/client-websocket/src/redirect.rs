@@ -0,0 +1,252 @@
+use http::Uri;
+use tokio_tungstenite::tungstenite::handshake::client::generate_key;
+
+use crate::error::{Error, Result, UrlError};
+
+/// Which underlying transport a `ws://`/`wss://` URI calls for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+  /// Plain, unencrypted TCP (`ws://`).
+  Plain,
+  /// TLS-wrapped TCP (`wss://`).
+  Tls,
+}
+
+/// Derives the [`Mode`] implied by a URI's scheme, rejecting anything but `ws`/`wss`.
+fn uri_mode(uri: &Uri) -> Result<Mode> {
+  match uri.scheme_str() {
+    Some("ws") => Ok(Mode::Plain),
+    Some("wss") => Ok(Mode::Tls),
+    _ => Err(Error::Url(UrlError::UnsupportedUrlScheme)),
+  }
+}
+
+/// Opt-in configuration for following HTTP redirects during the client handshake.
+///
+/// Redirect following is off by default: set `follow_redirects` to enable it.
+#[derive(Debug, Clone, Copy)]
+pub struct RedirectPolicy {
+  /// Whether 3xx responses to the handshake request should be followed at all.
+  pub follow_redirects: bool,
+  /// Maximum number of hops to follow before giving up with
+  /// [`UrlError::TooManyRedirects`].
+  pub max_redirects: usize,
+}
+
+impl Default for RedirectPolicy {
+  fn default() -> Self {
+    Self {
+      follow_redirects: false,
+      max_redirects: 3,
+    }
+  }
+}
+
+/// Inserted into the successful handshake response's extensions so callers can tell
+/// which URL was actually reached, in case redirects were followed.
+#[derive(Debug, Clone)]
+pub struct FinalUrl(pub Uri);
+
+fn is_redirect(status: http::StatusCode) -> bool {
+  matches!(status.as_u16(), 301 | 302 | 307 | 308)
+}
+
+/// Extracts and parses the `Location` header of a redirect response.
+///
+/// A missing or empty header, or one with characters other than visible ASCII, is
+/// [`UrlError::InvalidRedirectLocation`]. Any other value that is not a valid URI
+/// flows through the existing `InvalidUri` -> [`Error::HttpFormat`] conversion.
+fn location_uri(response: &http::Response<Option<Vec<u8>>>) -> Result<Uri> {
+  let location = response
+    .headers()
+    .get(http::header::LOCATION)
+    .ok_or(Error::Url(UrlError::InvalidRedirectLocation))?;
+  let location = location
+    .to_str()
+    .map_err(|_| Error::Url(UrlError::InvalidRedirectLocation))?;
+  if location.is_empty() {
+    return Err(Error::Url(UrlError::InvalidRedirectLocation));
+  }
+  Ok(location.parse::<Uri>()?)
+}
+
+/// Resolves a `Location` value against the URI it was reached from, honoring
+/// relative redirects (scheme/authority omitted) as well as absolute ones.
+fn resolve_redirect_uri(base: &Uri, location: &Uri) -> Result<Uri> {
+  if location.scheme().is_some() && location.authority().is_some() {
+    return Ok(location.clone());
+  }
+  let mut parts = location.clone().into_parts();
+  parts.scheme = base.scheme().cloned();
+  parts.authority = base.authority().cloned();
+  Uri::from_parts(parts).map_err(|_| Error::Url(UrlError::InvalidRedirectLocation))
+}
+
+/// Rebuilds the handshake request for a new hop: re-derives the `ws://`/`wss://` mode
+/// and `Host` header from `location`, generates a fresh `Sec-WebSocket-Key` (it's a
+/// nonce and must not be reused across handshake attempts), and carries over every
+/// other header from the previous attempt unchanged.
+pub(crate) fn rebuild_for_redirect(
+  request: &http::Request<()>,
+  location: &Uri,
+) -> Result<http::Request<()>> {
+  let new_uri = resolve_redirect_uri(request.uri(), location)?;
+  uri_mode(&new_uri)?;
+  let host = new_uri
+    .host()
+    .ok_or(Error::Url(UrlError::NoHostName))?;
+  let host_value = match new_uri.port() {
+    Some(port) => format!("{host}:{port}"),
+    None => host.to_string(),
+  };
+
+  let mut builder = http::Request::builder()
+    .method(request.method())
+    .uri(new_uri);
+  for (name, value) in request.headers() {
+    if name == http::header::HOST || name == http::header::SEC_WEBSOCKET_KEY {
+      continue;
+    }
+    builder = builder.header(name, value);
+  }
+  builder = builder
+    .header(http::header::HOST, host_value)
+    .header(http::header::SEC_WEBSOCKET_KEY, generate_key());
+  builder.body(()).map_err(Error::HttpFormat)
+}
+
+/// Runs a WebSocket handshake through `perform`, one attempt per hop, following HTTP
+/// redirects per `policy`.
+///
+/// `perform` executes one handshake attempt against the given request and returns the
+/// raw HTTP response; this function only owns the redirect bookkeeping (reading
+/// `Location`, rebuilding the request per hop, and enforcing the hop limit), so it can
+/// be reused regardless of how a single attempt actually talks to the network.
+///
+/// On success, the final response's extensions contain a [`FinalUrl`] reflecting the
+/// last URL reached.
+pub fn follow_redirects<F>(
+  mut request: http::Request<()>,
+  policy: RedirectPolicy,
+  mut perform: F,
+) -> Result<http::Response<Option<Vec<u8>>>>
+where
+  F: FnMut(&http::Request<()>) -> Result<http::Response<Option<Vec<u8>>>>,
+{
+  let mut uri = request.uri().clone();
+  let mut redirects = 0usize;
+  loop {
+    let mut response = perform(&request)?;
+    if !policy.follow_redirects || !is_redirect(response.status()) {
+      response.extensions_mut().insert(FinalUrl(uri));
+      return Ok(response);
+    }
+    if redirects >= policy.max_redirects {
+      return Err(Error::Url(UrlError::TooManyRedirects(policy.max_redirects)));
+    }
+    let location = location_uri(&response)?;
+    request = rebuild_for_redirect(&request, &location)?;
+    uri = request.uri().clone();
+    redirects += 1;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::RefCell;
+
+  fn initial_request() -> http::Request<()> {
+    http::Request::builder()
+      .method("GET")
+      .uri("ws://example.com/ws")
+      .header(http::header::HOST, "example.com")
+      .header(http::header::SEC_WEBSOCKET_KEY, generate_key())
+      .body(())
+      .unwrap()
+  }
+
+  fn redirect_response(location: &str) -> http::Response<Option<Vec<u8>>> {
+    http::Response::builder()
+      .status(301)
+      .header(http::header::LOCATION, location)
+      .body(None)
+      .unwrap()
+  }
+
+  fn ok_response() -> http::Response<Option<Vec<u8>>> {
+    http::Response::builder().status(101).body(None).unwrap()
+  }
+
+  #[test]
+  fn redirects_ignored_when_policy_disabled() {
+    let policy = RedirectPolicy {
+      follow_redirects: false,
+      max_redirects: 3,
+    };
+    let calls = RefCell::new(0);
+    let response = follow_redirects(initial_request(), policy, |_| {
+      *calls.borrow_mut() += 1;
+      Ok(redirect_response("ws://example.com/other"))
+    })
+    .unwrap();
+    assert_eq!(*calls.borrow(), 1);
+    assert_eq!(response.status(), 301);
+  }
+
+  #[test]
+  fn follows_up_to_the_limit_then_succeeds() {
+    let policy = RedirectPolicy {
+      follow_redirects: true,
+      max_redirects: 2,
+    };
+    let calls = RefCell::new(0);
+    let response = follow_redirects(initial_request(), policy, |_| {
+      let mut calls = calls.borrow_mut();
+      *calls += 1;
+      match *calls {
+        1 => Ok(redirect_response("ws://example.com/hop1")),
+        2 => Ok(redirect_response("ws://example.com/hop2")),
+        _ => Ok(ok_response()),
+      }
+    })
+    .unwrap();
+    assert_eq!(response.status(), 101);
+    let final_url = response.extensions().get::<FinalUrl>().unwrap();
+    assert_eq!(final_url.0, Uri::from_static("ws://example.com/hop2"));
+  }
+
+  #[test]
+  fn exceeding_the_limit_is_too_many_redirects() {
+    let policy = RedirectPolicy {
+      follow_redirects: true,
+      max_redirects: 1,
+    };
+    let calls = RefCell::new(0);
+    let err = follow_redirects(initial_request(), policy, |_| {
+      *calls.borrow_mut() += 1;
+      Ok(redirect_response("ws://example.com/again"))
+    })
+    .unwrap_err();
+    // One hop is allowed (2 attempts); the redirect on the 2nd response is what
+    // exceeds the limit, so a 3rd attempt is never made.
+    assert_eq!(*calls.borrow(), 2);
+    assert!(matches!(err, Error::Url(UrlError::TooManyRedirects(1))));
+  }
+
+  #[test]
+  fn missing_location_header_is_invalid_redirect_location() {
+    let policy = RedirectPolicy {
+      follow_redirects: true,
+      max_redirects: 3,
+    };
+    let err = follow_redirects(initial_request(), policy, |_| {
+      Ok(http::Response::builder().status(302).body(None).unwrap())
+    })
+    .unwrap_err();
+    assert!(matches!(
+      err,
+      Error::Url(UrlError::InvalidRedirectLocation)
+    ));
+  }
+}